@@ -10,7 +10,7 @@ fn lib_benchmark(c: &mut Criterion) {
 
     let mut group = c.benchmark_group("lib");
     group.bench_function("convert", |b| {
-        b.iter(|| bsp2svg::filter_and_sort_faces(&bsp, &bsp2svg::ProjectionAxis::Z))
+        b.iter(|| bsp2svg::filter_and_sort_faces(&bsp, bsp2svg::Projection::Z.view_dir()))
     });
     group.finish();
 }
@@ -3,40 +3,80 @@ use bspparser::helpers::{
     get_face_texture, get_face_vertice_indexes, get_face_vertices, read_texture_image, TextureScale,
 };
 use bspparser::{BspFile, Face};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::collections::HashMap;
 use std::io::{Read, Seek};
 
-pub enum ProjectionAxis {
-    X,
-    Y,
-    Z,
-}
+mod occlusion;
+mod projection;
+mod renderer;
+mod shading;
+pub use occlusion::OcclusionOptions;
+pub use projection::Projection;
+pub use renderer::{PngRenderer, RenderStats, Renderer, StatsRenderer, SvgRenderer};
+pub use shading::ShadingOptions;
 
 pub struct StuffToDraw {
     pub points: Vec<(f32, f32)>,
     pub texture_name: String,
     pub min_z: f32,
     pub max_z: f32,
+    pub intensity: f32,
+    pub occlusion: f32,
+}
+
+/// The face's plane normal, flipped to point outward when `face.side`
+/// marks it as facing the back of its plane.
+fn face_normal(bsp: &BspFile, face: &Face) -> (f32, f32, f32) {
+    let plane = &bsp.planes[face.plane_id as usize];
+    let normal = (plane.normal.x, plane.normal.y, plane.normal.z);
+
+    if face.side != 0 {
+        (-normal.0, -normal.1, -normal.2)
+    } else {
+        normal
+    }
 }
 
+/// Convenience wrapper that drives `convert_with` through an `SvgRenderer`
+/// with the `Z` projection, default shading and no ambient occlusion,
+/// writing `target/{filename}.svg`.
 pub fn convert<R>(r: &mut R, filename: &str) -> Result<()>
 where
     R: Read + Seek,
+{
+    convert_with(
+        r,
+        filename,
+        &Projection::Z,
+        SvgRenderer::new(filename),
+        &ShadingOptions::default(),
+        None,
+    )
+}
+
+pub fn convert_with<R, Rd>(
+    r: &mut R,
+    filename: &str,
+    projection: &Projection,
+    mut renderer: Rd,
+    shading_options: &ShadingOptions,
+    occlusion_options: Option<&OcclusionOptions>,
+) -> Result<Rd::Output>
+where
+    R: Read + Seek,
+    Rd: Renderer,
 {
     let bsp = BspFile::parse(r)?;
-    let axis = ProjectionAxis::Z;
 
     // 1. Projected vertices
-    // Project the 3D vertices onto a 2D plane based on the chosen projection axis:
-    // For z-axis projection (top-down view), use x and y coordinates.
-    // For y-axis projection (side view), use x and z coordinates.
-    // For x-axis projection (front view), use y and z coordinates.
-    #[rustfmt::skip]
-    let pvertices: Vec<(f32, f32)> = bsp.vertices.iter().map(|v| match axis {
-        ProjectionAxis::X => (v.y, v.z),
-        ProjectionAxis::Y => (v.x, v.z),
-        ProjectionAxis::Z => (v.x, -v.y), // flip y
-    }).collect();
+    // Flatten the 3D vertices onto a 2D plane using the chosen projection.
+    let pvertices: Vec<(f32, f32)> = bsp
+        .vertices
+        .iter()
+        .map(|v| projection.project((v.x, v.y, v.z)))
+        .collect();
 
     // get average color for each texture_name
     let mut color_per_tex_name: HashMap<String, (u32, u32, u32)> = HashMap::new();
@@ -62,10 +102,28 @@ where
         color_per_tex_name.insert(tex.name.to_string(), (r as u32, g as u32, b as u32));
     }
 
+    // Build the occlusion scene (every visible face triangulated into a
+    // fan) once, up front, so each face's AO pass can ray-test against it.
+    let occlusion_scene: Vec<occlusion::Triangle> = occlusion_options
+        .map(|_| {
+            filter_faces(&bsp)
+                .iter()
+                .flat_map(|face| {
+                    let vertices = get_face_vertices(&bsp, face)
+                        .iter()
+                        .map(|v| (v.x, v.y, v.z))
+                        .collect::<Vec<(f32, f32, f32)>>();
+                    occlusion::triangulate_fan(&vertices)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut occlusion_rng = occlusion_options.map(|o| StdRng::seed_from_u64(o.seed));
+
     // 2. Generate Polygons paths
     // Filter and sort faces based on the projection axis to ensure correct rendering order.
     // For each face, generate a polygon using the projected_vertices.
-    let stuff_to_draw: Vec<StuffToDraw> = filter_and_sort_faces(&bsp, &axis)
+    let stuff_to_draw: Vec<StuffToDraw> = filter_and_sort_faces(&bsp, projection.view_dir())
         .iter()
         .map(|face| {
             let points = get_face_vertice_indexes(&bsp, face)
@@ -73,56 +131,56 @@ where
                 .map(|vertex_index| pvertices[*vertex_index as usize])
                 .collect::<Vec<(f32, f32)>>();
             let texture_name = get_face_texture(&bsp, face).name.to_string();
+            let vertices_3d = get_face_vertices(&bsp, face)
+                .iter()
+                .map(|v| (v.x, v.y, v.z))
+                .collect::<Vec<(f32, f32, f32)>>();
+            let normal = face_normal(&bsp, face);
+
+            let occlusion = match (occlusion_options, &mut occlusion_rng) {
+                (Some(options), Some(rng)) => {
+                    let centroid = occlusion::centroid(&vertices_3d);
+                    let offset = (
+                        normal.0 * 0.5,
+                        normal.1 * 0.5,
+                        normal.2 * 0.5,
+                    );
+                    let origin = (
+                        centroid.0 + offset.0,
+                        centroid.1 + offset.1,
+                        centroid.2 + offset.2,
+                    );
+                    occlusion::occlusion_factor(origin, normal, &occlusion_scene, options, rng)
+                }
+                _ => 0.0,
+            };
+
             StuffToDraw {
                 points: points.clone(),
                 texture_name,
-                max_z: get_face_vertices(&bsp, face)
+                max_z: vertices_3d
                     .iter()
-                    .map(|v| v.z)
+                    .map(|v| v.2)
                     .reduce(f32::max)
                     .unwrap(),
-                min_z: get_face_vertices(&bsp, face)
+                min_z: vertices_3d
                     .iter()
-                    .map(|v| v.z)
+                    .map(|v| v.2)
                     .reduce(f32::min)
                     .unwrap(),
+                intensity: shading::intensity(normal, shading_options),
+                occlusion,
             }
         })
         // skip empty
         .filter(|s| !s.points.is_empty())
         .collect();
 
-    // 3. Generate SVG
-    // add polygons and other necessary elements (e.g., background, borders).
+    // 3. Drive the renderer
+    // Compute the viewbox once, then feed each face to the renderer in the
+    // painter's-algorithm order `filter_and_sort_faces` already produced.
     let padding = 100.0;
 
-    let mut bsp_group = svg::node::element::Group::new().set("id", "bsp_ref");
-
-    for item in stuff_to_draw.iter() {
-        let points_str = item
-            .points
-            .iter()
-            .map(|(x, y)| format!("{},{}", x, y))
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        let fill_color = color_per_tex_name
-            .get(&item.texture_name)
-            .unwrap_or(&(255, 255, 255));
-
-        // convert to hex representation
-        let fill_color = format!(
-            "#{:02x}{:02x}{:02x}",
-            fill_color.0, fill_color.1, fill_color.2
-        );
-
-        bsp_group = bsp_group.add(
-            svg::node::element::Polygon::new()
-                .set("points", points_str)
-                .set("fill", fill_color),
-        );
-    }
-
     #[rustfmt::skip]
     let bounds = (
         pvertices.clone().into_iter().map(|(x, _)| x).reduce(f32::min).unwrap(),
@@ -138,38 +196,21 @@ where
         bounds.3 - bounds.2 + 2. * padding,
     );
 
-    let mut doc = svg::Document::new()
-        .set(
-            "viewBox",
-            format!("{} {} {} {}", viewbox.0, viewbox.1, viewbox.2, viewbox.3),
-        )
-        .add(
-            // background
-            svg::node::element::Rectangle::new()
-                .set("x", viewbox.0)
-                .set("y", viewbox.1)
-                .set("width", viewbox.2)
-                .set("height", viewbox.3)
-                .set("fill", "black"),
-        )
-        .add(svg::node::element::Definitions::new().add(bsp_group));
-
-    doc = doc.add(
-        svg::node::element::Use::new()
-            .set("href", "#bsp_ref")
-            .set("stroke", "black")
-            .set("stroke-width", 10)
-            .set("stroke-miterlimit", 0),
-    );
-    doc = doc.add(
-        svg::node::element::Use::new()
-            .set("href", "#bsp_ref")
-            .set("fill", "#eee")
-            .set("stroke", "black")
-            .set("stroke-width", "0.5"),
-    );
+    renderer.begin(viewbox);
 
-    svg::save(format!("target/{filename}.svg"), &doc)?;
+    for item in stuff_to_draw.iter() {
+        let fill_color = *color_per_tex_name
+            .get(&item.texture_name)
+            .unwrap_or(&(255, 255, 255));
+        let fill_color = shading::shade(fill_color, item.intensity);
+        let fill_color = match occlusion_options {
+            Some(options) => occlusion::shade(fill_color, item.occlusion, options),
+            None => fill_color,
+        };
+        let fill_color = (fill_color.0 as u8, fill_color.1 as u8, fill_color.2 as u8);
+
+        renderer.draw_polygon(&item.points, fill_color, (item.min_z, item.max_z));
+    }
 
     let unique = stuff_to_draw
         .iter()
@@ -178,22 +219,26 @@ where
     dbg!(&filename);
     dbg!(&unique);
 
-    Ok(())
+    renderer.finish()
 }
 
-pub fn filter_and_sort_faces(bsp: &BspFile, axis: &ProjectionAxis) -> Vec<Face> {
+/// Sorts faces into painter's-algorithm (back-to-front) order along
+/// `view_dir`: each face's distance is the minimum, over its vertices, of
+/// the dot product with `view_dir`. For an axis-aligned `view_dir` (e.g.
+/// `(0, 0, 1)`) this is just that axis's coordinate, reproducing the
+/// original per-axis sort.
+pub fn filter_and_sort_faces(bsp: &BspFile, view_dir: (f32, f32, f32)) -> Vec<Face> {
     let mut faces: Vec<Face> = filter_faces(bsp);
 
     let minimums: HashMap<usize, f32> = {
         let mut result = HashMap::new();
         for face in faces.iter() {
             let vertices = get_face_vertices(bsp, face);
-            let min = match axis {
-                ProjectionAxis::X => vertices.iter().map(|v| v.x).reduce(f32::min),
-                ProjectionAxis::Y => vertices.iter().map(|v| v.y).reduce(f32::min),
-                ProjectionAxis::Z => vertices.iter().map(|v| v.z).reduce(f32::min),
-            }
-            .unwrap();
+            let min = vertices
+                .iter()
+                .map(|v| v.x * view_dir.0 + v.y * view_dir.1 + v.z * view_dir.2)
+                .reduce(f32::min)
+                .unwrap();
             result.insert(face.edge_list_index as usize, min);
         }
         result
@@ -0,0 +1,305 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+
+type Vec3 = (f32, f32, f32);
+
+/// Controls the Monte-Carlo ambient-occlusion pass: how many hemisphere
+/// samples each face casts, how far a sample ray can travel before it no
+/// longer counts as an occluder, and how strongly the result darkens a
+/// face's color.
+pub struct OcclusionOptions {
+    pub samples: usize,
+    pub max_distance: f32,
+    pub strength: f32,
+    /// Seeds the RNG so a given input always produces the same output.
+    pub seed: u64,
+}
+
+impl Default for OcclusionOptions {
+    fn default() -> Self {
+        Self {
+            samples: 64,
+            max_distance: 256.0,
+            strength: 0.5,
+            seed: 0,
+        }
+    }
+}
+
+/// A world-space triangle, used only as an occluder for ray casts — not
+/// drawn directly.
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+/// Fans a (possibly non-triangular) convex face polygon into triangles
+/// sharing its first vertex, same as a face's points are already assumed
+/// convex for rendering.
+pub fn triangulate_fan(vertices: &[Vec3]) -> Vec<Triangle> {
+    let mut triangles = Vec::new();
+    if vertices.len() < 3 {
+        return triangles;
+    }
+
+    for i in 1..vertices.len() - 1 {
+        triangles.push(Triangle {
+            a: vertices[0],
+            b: vertices[i],
+            c: vertices[i + 1],
+        });
+    }
+
+    triangles
+}
+
+pub fn centroid(vertices: &[Vec3]) -> Vec3 {
+    let n = vertices.len() as f32;
+    let sum = vertices
+        .iter()
+        .fold((0., 0., 0.), |acc, v| (acc.0 + v.0, acc.1 + v.1, acc.2 + v.2));
+    (sum.0 / n, sum.1 / n, sum.2 / n)
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    (a.0 - b.0, a.1 - b.1, a.2 - b.2)
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+fn scale(a: Vec3, s: f32) -> Vec3 {
+    (a.0 * s, a.1 * s, a.2 * s)
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a.0 * b.0 + a.1 * b.1 + a.2 * b.2
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    (
+        a.1 * b.2 - a.2 * b.1,
+        a.2 * b.0 - a.0 * b.2,
+        a.0 * b.1 - a.1 * b.0,
+    )
+}
+
+fn normalize(a: Vec3) -> Vec3 {
+    let len = dot(a, a).sqrt();
+    if len == 0. {
+        a
+    } else {
+        scale(a, 1. / len)
+    }
+}
+
+/// Cosine-weighted sample of the hemisphere about `normal` (Malley's
+/// method): uniform on a disk, then projected up onto the hemisphere.
+fn sample_hemisphere(normal: Vec3, rng: &mut StdRng) -> Vec3 {
+    let w = normalize(normal);
+    let up = if w.0.abs() > 0.9 { (0., 1., 0.) } else { (1., 0., 0.) };
+    let u = normalize(cross(up, w));
+    let v = cross(w, u);
+
+    let r1: f32 = rng.gen();
+    let r2: f32 = rng.gen();
+    let r = r1.sqrt();
+    let theta = 2.0 * std::f32::consts::PI * r2;
+
+    let x = r * theta.cos();
+    let y = r * theta.sin();
+    let z = (1.0 - r1).max(0.).sqrt();
+
+    add(add(scale(u, x), scale(v, y)), scale(w, z))
+}
+
+/// Möller–Trumbore ray/triangle intersection. Returns the hit distance
+/// along `dir` if the ray hits the triangle's front or back face within
+/// `(0, f32::INFINITY)`.
+fn ray_triangle_hit(origin: Vec3, dir: Vec3, tri: &Triangle) -> Option<f32> {
+    const EPSILON: f32 = 1e-6;
+
+    let edge1 = sub(tri.b, tri.a);
+    let edge2 = sub(tri.c, tri.a);
+    let h = cross(dir, edge2);
+    let a = dot(edge1, h);
+    if a.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / a;
+    let s = sub(origin, tri.a);
+    let u = f * dot(s, h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = cross(s, edge1);
+    let v = f * dot(dir, q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = f * dot(edge2, q);
+    if t > EPSILON {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Casts `options.samples` cosine-weighted rays from `origin` (already
+/// nudged off the surface along `normal`) over the hemisphere, counting how
+/// many hit an occluder within `options.max_distance`. Returns
+/// `hits / samples`, the raw occlusion factor in `0.0..=1.0`.
+pub fn occlusion_factor(
+    origin: Vec3,
+    normal: Vec3,
+    scene: &[Triangle],
+    options: &OcclusionOptions,
+    rng: &mut StdRng,
+) -> f32 {
+    if options.samples == 0 {
+        return 0.0;
+    }
+
+    let mut hits = 0usize;
+    for _ in 0..options.samples {
+        let dir = sample_hemisphere(normal, rng);
+        let hit = scene
+            .iter()
+            .filter_map(|tri| ray_triangle_hit(origin, dir, tri))
+            .any(|t| t <= options.max_distance);
+        if hit {
+            hits += 1;
+        }
+    }
+
+    hits as f32 / options.samples as f32
+}
+
+/// `(r, g, b) * (1 - strength * occlusion)`
+pub fn shade(color: (u32, u32, u32), occlusion: f32, options: &OcclusionOptions) -> (u32, u32, u32) {
+    let factor = 1.0 - options.strength * occlusion;
+    (
+        (color.0 as f32 * factor).clamp(0.0, 255.0) as u32,
+        (color.1 as f32 * factor).clamp(0.0, 255.0) as u32,
+        (color.2 as f32 * factor).clamp(0.0, 255.0) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn centroid_of_a_square_is_its_middle() {
+        let square = [(0., 0., 0.), (2., 0., 0.), (2., 2., 0.), (0., 2., 0.)];
+        assert_eq!(centroid(&square), (1., 1., 0.));
+    }
+
+    #[test]
+    fn triangulate_fan_covers_a_quad_with_two_triangles() {
+        let quad = [(0., 0., 0.), (1., 0., 0.), (1., 1., 0.), (0., 1., 0.)];
+        let triangles = triangulate_fan(&quad);
+        assert_eq!(triangles.len(), 2);
+    }
+
+    #[test]
+    fn triangulate_fan_of_a_degenerate_polygon_is_empty() {
+        assert!(triangulate_fan(&[(0., 0., 0.), (1., 0., 0.)]).is_empty());
+    }
+
+    #[test]
+    fn ray_hits_triangle_directly_ahead() {
+        let tri = Triangle {
+            a: (-1., -1., 5.),
+            b: (1., -1., 5.),
+            c: (0., 1., 5.),
+        };
+        let hit = ray_triangle_hit((0., 0., 0.), (0., 0., 1.), &tri);
+        assert_eq!(hit, Some(5.0));
+    }
+
+    #[test]
+    fn ray_misses_triangle_behind_its_plane() {
+        let tri = Triangle {
+            a: (-1., -1., -5.),
+            b: (1., -1., -5.),
+            c: (0., 1., -5.),
+        };
+        assert!(ray_triangle_hit((0., 0., 0.), (0., 0., 1.), &tri).is_none());
+    }
+
+    #[test]
+    fn face_fully_enclosed_in_a_box_is_heavily_occluded() {
+        // A face at the center of a cube made of six triangulated quads is
+        // surrounded on every side, so nearly every sampled ray should hit
+        // a wall within range.
+        let quads: [[Vec3; 4]; 6] = [
+            [(-10., -10., -10.), (10., -10., -10.), (10., 10., -10.), (-10., 10., -10.)],
+            [(-10., -10., 10.), (10., -10., 10.), (10., 10., 10.), (-10., 10., 10.)],
+            [(-10., -10., -10.), (-10., 10., -10.), (-10., 10., 10.), (-10., -10., 10.)],
+            [(10., -10., -10.), (10., 10., -10.), (10., 10., 10.), (10., -10., 10.)],
+            [(-10., -10., -10.), (10., -10., -10.), (10., -10., 10.), (-10., -10., 10.)],
+            [(-10., 10., -10.), (10., 10., -10.), (10., 10., 10.), (-10., 10., 10.)],
+        ];
+        let scene: Vec<Triangle> = quads.iter().flat_map(|q| triangulate_fan(q)).collect();
+
+        let options = OcclusionOptions {
+            samples: 64,
+            max_distance: 50.0,
+            strength: 1.0,
+            seed: 42,
+        };
+        let mut rng = StdRng::seed_from_u64(options.seed);
+
+        let factor = occlusion_factor((0., 0., 0.), (0., 0., 1.), &scene, &options, &mut rng);
+
+        assert!(factor > 0.9, "expected near-total occlusion, got {factor}");
+    }
+
+    #[test]
+    fn face_with_empty_scene_is_never_occluded() {
+        let options = OcclusionOptions::default();
+        let mut rng = StdRng::seed_from_u64(options.seed);
+
+        let factor = occlusion_factor((0., 0., 0.), (0., 0., 1.), &[], &options, &mut rng);
+
+        assert_eq!(factor, 0.0);
+    }
+
+    #[test]
+    fn same_seed_gives_same_occlusion_factor() {
+        let tri = Triangle {
+            a: (-5., -5., 5.),
+            b: (5., -5., 5.),
+            c: (0., 5., 5.),
+        };
+        let scene = vec![tri];
+        let options = OcclusionOptions::default();
+
+        let mut rng_a = StdRng::seed_from_u64(7);
+        let a = occlusion_factor((0., 0., 0.), (0., 0., 1.), &scene, &options, &mut rng_a);
+
+        let mut rng_b = StdRng::seed_from_u64(7);
+        let b = occlusion_factor((0., 0., 0.), (0., 0., 1.), &scene, &options, &mut rng_b);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn shade_clamps_instead_of_wrapping_when_strength_is_negative() {
+        let options = OcclusionOptions {
+            strength: -1.5,
+            ..OcclusionOptions::default()
+        };
+
+        let shaded = shade((255, 255, 255), 1.0, &options);
+
+        assert_eq!(shaded, (255, 255, 255));
+    }
+}
@@ -0,0 +1,91 @@
+/// How 3D BSP vertices are flattened into the 2D plane `convert` draws.
+///
+/// `X`/`Y`/`Z` are the original orthographic axis-aligned views. `Oblique`
+/// adds a parameterized isometric-style view: vertices are rotated by
+/// `yaw` around the z axis, then tilted by `pitch`, before dropping the
+/// resulting depth axis — giving a 2.5D view that conveys vertical
+/// structure without leaving the SVG/PNG pipeline.
+pub enum Projection {
+    X,
+    Y,
+    Z,
+    Oblique { yaw: f32, pitch: f32 },
+}
+
+impl Projection {
+    /// Flattens a world-space vertex to 2D.
+    pub fn project(&self, v: (f32, f32, f32)) -> (f32, f32) {
+        match self {
+            Projection::X => (v.1, v.2),
+            Projection::Y => (v.0, v.2),
+            Projection::Z => (v.0, -v.1), // flip y
+            Projection::Oblique { yaw, pitch } => {
+                let (sy, cy) = yaw.sin_cos();
+                let (sp, cp) = pitch.sin_cos();
+                let x = v.0 * cy - v.1 * sy;
+                let y = (v.0 * sy + v.1 * cy) * cp - v.2 * sp;
+                (x, y)
+            }
+        }
+    }
+
+    /// The world-space direction depth increases along for this
+    /// projection — the normal of the plane vertices get flattened onto.
+    /// Sorting faces by the dot product of their nearest vertex with this
+    /// vector reproduces painter's-algorithm order for any projection,
+    /// axis-aligned or oblique.
+    ///
+    /// For `Oblique`, this is the cross product of the two 3D directions
+    /// that `project` maps onto the x' and y' screen axes; at
+    /// `yaw = pitch = 0.0` it reduces to `Z`'s `(0, 0, 1)`.
+    pub fn view_dir(&self) -> (f32, f32, f32) {
+        match self {
+            Projection::X => (1., 0., 0.),
+            Projection::Y => (0., 1., 0.),
+            Projection::Z => (0., 0., 1.),
+            Projection::Oblique { yaw, pitch } => {
+                let (sy, cy) = yaw.sin_cos();
+                let (sp, cp) = pitch.sin_cos();
+                (sy * sp, cy * sp, cp)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn axis_aligned_projections_match_their_original_formulas() {
+        let v = (1., 2., 3.);
+        assert_close(Projection::X.project(v), (2., 3.));
+        assert_close(Projection::Y.project(v), (1., 3.));
+        assert_close(Projection::Z.project(v), (1., -2.));
+    }
+
+    #[test]
+    fn oblique_at_zero_yaw_pitch_keeps_x_y_unflipped_and_matches_z_view_dir() {
+        let projection = Projection::Oblique { yaw: 0., pitch: 0. };
+        let v = (1., 2., 3.);
+
+        // Unlike `Z`, the oblique formula does not flip y.
+        assert_close(projection.project(v), (1., 2.));
+        assert_eq!(projection.view_dir(), Projection::Z.view_dir());
+    }
+
+    #[test]
+    fn oblique_view_dir_is_a_unit_vector() {
+        let projection = Projection::Oblique {
+            yaw: 0.7,
+            pitch: 0.4,
+        };
+        let (x, y, z) = projection.view_dir();
+
+        assert!((x * x + y * y + z * z - 1.0).abs() < 1e-5);
+    }
+}
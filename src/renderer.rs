@@ -0,0 +1,490 @@
+use anyhow::Result;
+use image::{Rgba, RgbaImage};
+use lyon::math::point;
+use lyon::path::Path;
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, FillVertexConstructor, VertexBuffers,
+};
+
+/// A target that the projected, sorted, and colored BSP faces can be drawn
+/// into. `convert` drives a `Renderer` through one `begin`/`draw_polygon*`/
+/// `finish` pass; everything upstream of this trait (projection, face
+/// sorting, texture color averaging) stays renderer-agnostic.
+///
+/// Modeled after ruffle's `RenderBackend`: a small, ordered set of calls the
+/// caller makes once per frame, with the backend owning whatever state it
+/// needs to turn those calls into a concrete output.
+pub trait Renderer {
+    /// What `finish` hands back, e.g. `()` for a renderer that writes a file
+    /// as a side effect, or an in-memory buffer for one that doesn't.
+    type Output;
+
+    /// Called once before any polygons are drawn. `viewbox` is
+    /// `(min_x, min_y, width, height)` in world/projected units, already
+    /// padded.
+    fn begin(&mut self, viewbox: (f32, f32, f32, f32));
+
+    /// Called once per face, in the painter's-algorithm order produced by
+    /// `filter_and_sort_faces`. `points` are projected 2D vertices, `fill`
+    /// is the face's averaged texture color after shading (and ambient
+    /// occlusion, when enabled), and `z_range` is its
+    /// `(min_z, max_z)` before projection — useful for renderers that want
+    /// to convey height (e.g. drop shadows sized by vertical extent).
+    fn draw_polygon(&mut self, points: &[(f32, f32)], fill: (u8, u8, u8), z_range: (f32, f32));
+
+    /// Called once after all polygons have been drawn.
+    fn finish(self) -> Result<Self::Output>;
+}
+
+struct SvgFace {
+    points: Vec<(f32, f32)>,
+    fill: (u8, u8, u8),
+    z_range: (f32, f32),
+}
+
+/// Reproduces the SVG output `convert` used to build inline, optionally
+/// grouping faces into z-bands that each get their own drop-shadow
+/// `<filter>`: a background rectangle sized to the viewbox, one `<defs>`
+/// group of polygons per band reused via a filtered shadow `<use>` layer
+/// plus the original outline/fill `<use>` layers, saved to
+/// `target/{filename}.svg`.
+///
+/// Faces are buffered during `draw_polygon` and only grouped into bands
+/// once `finish` knows the full z range, so polygons don't need to be
+/// drawn in z order. Bands are emitted from lowest to highest; within a
+/// band, faces keep the order they were drawn in (the painter's-algorithm
+/// order from `filter_and_sort_faces`). Across bands that order is not
+/// kept: a face is drawn with its band, not at its sorted position.
+pub struct SvgRenderer {
+    filename: String,
+    viewbox: (f32, f32, f32, f32),
+    shadow_bands: usize,
+    faces: Vec<SvgFace>,
+}
+
+impl SvgRenderer {
+    pub fn new(filename: &str) -> Self {
+        Self {
+            filename: filename.to_string(),
+            viewbox: (0., 0., 0., 0.),
+            shadow_bands: 1,
+            faces: Vec::new(),
+        }
+    }
+
+    /// Sets how many z-bands faces are bucketed into for drop shadows.
+    /// `0` or `1` disables the shadow filters entirely, reproducing the
+    /// original flat output.
+    pub fn with_shadow_bands(mut self, bands: usize) -> Self {
+        self.shadow_bands = bands;
+        self
+    }
+}
+
+impl Renderer for SvgRenderer {
+    type Output = ();
+
+    fn begin(&mut self, viewbox: (f32, f32, f32, f32)) {
+        self.viewbox = viewbox;
+    }
+
+    fn draw_polygon(&mut self, points: &[(f32, f32)], fill: (u8, u8, u8), z_range: (f32, f32)) {
+        self.faces.push(SvgFace {
+            points: points.to_vec(),
+            fill,
+            z_range,
+        });
+    }
+
+    fn finish(self) -> Result<()> {
+        let viewbox = self.viewbox;
+
+        let mut doc = svg::Document::new().set(
+            "viewBox",
+            format!("{} {} {} {}", viewbox.0, viewbox.1, viewbox.2, viewbox.3),
+        );
+        doc = doc.add(
+            // background
+            svg::node::element::Rectangle::new()
+                .set("x", viewbox.0)
+                .set("y", viewbox.1)
+                .set("width", viewbox.2)
+                .set("height", viewbox.3)
+                .set("fill", "black"),
+        );
+
+        let bands = bucket_into_bands(&self.faces, self.shadow_bands);
+
+        for (band_index, band_faces) in bands.iter().enumerate() {
+            if band_faces.is_empty() {
+                continue;
+            }
+
+            let group_id = format!("bsp_ref_{band_index}");
+            let mut group = svg::node::element::Group::new().set("id", group_id.clone());
+
+            for face in band_faces.iter() {
+                let points_str = face
+                    .points
+                    .iter()
+                    .map(|(x, y)| format!("{},{}", x, y))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                let fill_color = format!("#{:02x}{:02x}{:02x}", face.fill.0, face.fill.1, face.fill.2);
+
+                group = group.add(
+                    svg::node::element::Polygon::new()
+                        .set("points", points_str)
+                        .set("fill", fill_color),
+                );
+            }
+
+            doc = doc.add(svg::node::element::Definitions::new().add(group));
+
+            if bands.len() > 1 {
+                let filter_id = format!("bsp_shadow_{band_index}");
+                let band_scale = band_index as f32 / (bands.len() - 1).max(1) as f32;
+                let blur = 1.0 + band_scale * 6.0;
+                let offset = 2.0 + band_scale * 10.0;
+
+                doc = doc.add(drop_shadow_filter(&filter_id, blur, offset));
+                doc = doc.add(
+                    svg::node::element::Use::new()
+                        .set("href", format!("#{group_id}"))
+                        .set("filter", format!("url(#{filter_id})")),
+                );
+            }
+
+            doc = doc.add(
+                svg::node::element::Use::new()
+                    .set("href", format!("#{group_id}"))
+                    .set("stroke", "black")
+                    .set("stroke-width", 10)
+                    .set("stroke-miterlimit", 0),
+            );
+            doc = doc.add(
+                svg::node::element::Use::new()
+                    .set("href", format!("#{group_id}"))
+                    .set("fill", "#eee")
+                    .set("stroke", "black")
+                    .set("stroke-width", "0.5"),
+            );
+        }
+
+        svg::save(format!("target/{}.svg", self.filename), &doc)?;
+
+        Ok(())
+    }
+}
+
+/// Buckets faces into `bands` groups by the midpoint of each face's
+/// `z_range`, evenly spanning the full z extent of all faces. Returns
+/// `bands.max(1)` groups, each preserving the input (painter's-algorithm)
+/// order of its members.
+fn bucket_into_bands(faces: &[SvgFace], bands: usize) -> Vec<Vec<&SvgFace>> {
+    let bands = bands.max(1);
+    let mut grouped: Vec<Vec<&SvgFace>> = (0..bands).map(|_| Vec::new()).collect();
+
+    if bands == 1 || faces.is_empty() {
+        grouped[0].extend(faces.iter());
+        return grouped;
+    }
+
+    let min_z = faces
+        .iter()
+        .map(|f| f.z_range.0)
+        .reduce(f32::min)
+        .unwrap();
+    let max_z = faces
+        .iter()
+        .map(|f| f.z_range.1)
+        .reduce(f32::max)
+        .unwrap();
+    let span = (max_z - min_z).max(f32::EPSILON);
+
+    for face in faces {
+        let mid_z = (face.z_range.0 + face.z_range.1) / 2.0;
+        let band = (((mid_z - min_z) / span) * bands as f32) as usize;
+        grouped[band.min(bands - 1)].push(face);
+    }
+
+    grouped
+}
+
+/// A drop shadow built from primitives (`feGaussianBlur` -> `feOffset` ->
+/// `feMerge`), equivalent to `feDropShadow` but matching what the rest of
+/// this module already emits by hand.
+fn drop_shadow_filter(id: &str, blur: f32, offset: f32) -> svg::node::element::Element {
+    svg::node::element::Element::new("filter")
+        .set("id", id.to_string())
+        .set("x", "-50%")
+        .set("y", "-50%")
+        .set("width", "200%")
+        .set("height", "200%")
+        .add(
+            svg::node::element::Element::new("feGaussianBlur")
+                .set("in", "SourceAlpha")
+                .set("stdDeviation", blur)
+                .set("result", "blur"),
+        )
+        .add(
+            svg::node::element::Element::new("feOffset")
+                .set("in", "blur")
+                .set("dx", offset)
+                .set("dy", offset)
+                .set("result", "offsetBlur"),
+        )
+        .add(
+            svg::node::element::Element::new("feMerge")
+                .add(svg::node::element::Element::new("feMergeNode").set("in", "offsetBlur"))
+                .add(svg::node::element::Element::new("feMergeNode").set("in", "SourceGraphic")),
+        )
+}
+
+/// Tallies polygons and bounds without touching disk or pulling in a drawing
+/// library. Exists mainly as a second `Renderer` impl, proving `convert`
+/// doesn't secretly depend on SVG internals.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RenderStats {
+    pub polygon_count: usize,
+    pub vertex_count: usize,
+    pub viewbox: (f32, f32, f32, f32),
+}
+
+#[derive(Default)]
+pub struct StatsRenderer {
+    stats: RenderStats,
+}
+
+impl StatsRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Renderer for StatsRenderer {
+    type Output = RenderStats;
+
+    fn begin(&mut self, viewbox: (f32, f32, f32, f32)) {
+        self.stats.viewbox = viewbox;
+    }
+
+    fn draw_polygon(&mut self, points: &[(f32, f32)], _fill: (u8, u8, u8), _z_range: (f32, f32)) {
+        self.stats.polygon_count += 1;
+        self.stats.vertex_count += points.len();
+    }
+
+    fn finish(self) -> Result<RenderStats> {
+        Ok(self.stats)
+    }
+}
+
+struct TessVertex {
+    x: f32,
+    y: f32,
+}
+
+struct TessVertexCtor;
+
+impl FillVertexConstructor<TessVertex> for TessVertexCtor {
+    fn new_vertex(&mut self, vertex: FillVertex) -> TessVertex {
+        let p = vertex.position();
+        TessVertex { x: p.x, y: p.y }
+    }
+}
+
+/// Rasterizes faces to a PNG instead of an SVG. Each polygon is tessellated
+/// into triangles with `lyon`, then scan-filled straight into an
+/// `image::RgbaImage`. Painter's-algorithm correctness comes for free: faces
+/// arrive from `convert` in the same view-direction order `filter_and_sort_faces`
+/// already sorted them into, so later (nearer) triangles simply overwrite
+/// earlier (farther) ones.
+pub struct PngRenderer {
+    filename: String,
+    width: u32,
+    height: u32,
+    viewbox: (f32, f32, f32, f32),
+    image: RgbaImage,
+}
+
+impl PngRenderer {
+    pub fn new(filename: &str, width: u32, height: u32) -> Self {
+        Self {
+            filename: filename.to_string(),
+            width: width.max(1),
+            height: height.max(1),
+            viewbox: (0., 0., 0., 0.),
+            image: RgbaImage::new(width.max(1), height.max(1)),
+        }
+    }
+
+    fn to_pixel(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (vx, vy, vw, vh) = self.viewbox;
+        (
+            (x - vx) / vw * self.width as f32,
+            (y - vy) / vh * self.height as f32,
+        )
+    }
+
+    fn fill_triangle(&mut self, a: (f32, f32), b: (f32, f32), c: (f32, f32), color: Rgba<u8>) {
+        let edge = |p: (f32, f32), q: (f32, f32), r: (f32, f32)| {
+            (r.0 - p.0) * (q.1 - p.1) - (r.1 - p.1) * (q.0 - p.0)
+        };
+
+        let area = edge(a, b, c);
+        if area == 0. {
+            return;
+        }
+
+        let min_x = a.0.min(b.0).min(c.0).floor().max(0.) as u32;
+        let max_x = (a.0.max(b.0).max(c.0).ceil() as u32).min(self.width);
+        let min_y = a.1.min(b.1).min(c.1).floor().max(0.) as u32;
+        let max_y = (a.1.max(b.1).max(c.1).ceil() as u32).min(self.height);
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                // barycentric test: sample pixel centers
+                let p = (px as f32 + 0.5, py as f32 + 0.5);
+                let w0 = edge(b, c, p);
+                let w1 = edge(c, a, p);
+                let w2 = edge(a, b, p);
+
+                let inside =
+                    (w0 >= 0. && w1 >= 0. && w2 >= 0.) || (w0 <= 0. && w1 <= 0. && w2 <= 0.);
+                if inside {
+                    self.image.put_pixel(px, py, color);
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for PngRenderer {
+    type Output = ();
+
+    fn begin(&mut self, viewbox: (f32, f32, f32, f32)) {
+        self.viewbox = viewbox;
+        for pixel in self.image.pixels_mut() {
+            *pixel = Rgba([0, 0, 0, 255]);
+        }
+    }
+
+    fn draw_polygon(&mut self, points: &[(f32, f32)], fill: (u8, u8, u8), _z_range: (f32, f32)) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let mut path_builder = Path::builder();
+        path_builder.begin(point(points[0].0, points[0].1));
+        for p in &points[1..] {
+            path_builder.line_to(point(p.0, p.1));
+        }
+        path_builder.end(true);
+        let path = path_builder.build();
+
+        let mut buffers: VertexBuffers<TessVertex, u16> = VertexBuffers::new();
+        let mut tessellator = FillTessellator::new();
+        let tessellated = tessellator.tessellate_path(
+            &path,
+            &FillOptions::default(),
+            &mut BuffersBuilder::new(&mut buffers, TessVertexCtor),
+        );
+        if tessellated.is_err() {
+            return;
+        }
+
+        let color = Rgba([fill.0, fill.1, fill.2, 255]);
+
+        for tri in buffers.indices.chunks_exact(3) {
+            let va = &buffers.vertices[tri[0] as usize];
+            let vb = &buffers.vertices[tri[1] as usize];
+            let vc = &buffers.vertices[tri[2] as usize];
+            let a = self.to_pixel((va.x, va.y));
+            let b = self.to_pixel((vb.x, vb.y));
+            let c = self.to_pixel((vc.x, vc.y));
+            self.fill_triangle(a, b, c, color);
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        self.image.save(format!("target/{}.png", self.filename))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(min_z: f32, max_z: f32) -> SvgFace {
+        SvgFace {
+            points: vec![],
+            fill: (0, 0, 0),
+            z_range: (min_z, max_z),
+        }
+    }
+
+    #[test]
+    fn bucket_into_bands_with_one_band_keeps_everything_together() {
+        let faces = vec![face(0., 10.), face(50., 100.)];
+        let bands = bucket_into_bands(&faces, 1);
+
+        assert_eq!(bands.len(), 1);
+        assert_eq!(bands[0].len(), 2);
+    }
+
+    #[test]
+    fn bucket_into_bands_assigns_low_and_high_faces_to_different_bands() {
+        let faces = vec![face(0., 0.), face(100., 100.)];
+        let bands = bucket_into_bands(&faces, 4);
+
+        assert_eq!(bands.len(), 4);
+        assert_eq!(bands[0].len(), 1);
+        assert_eq!(bands[0][0].z_range, (0., 0.));
+        assert_eq!(bands[3].len(), 1);
+        assert_eq!(bands[3][0].z_range, (100., 100.));
+    }
+
+    #[test]
+    fn bucket_into_bands_with_no_faces_is_empty() {
+        let bands = bucket_into_bands(&[], 4);
+
+        assert_eq!(bands.len(), 4);
+        assert!(bands.iter().all(|b| b.is_empty()));
+    }
+
+    #[test]
+    fn stats_renderer_tallies_polygons_and_vertices() {
+        let mut renderer = StatsRenderer::new();
+        renderer.begin((0., 0., 100., 100.));
+        renderer.draw_polygon(&[(0., 0.), (1., 0.), (1., 1.)], (255, 0, 0), (0., 10.));
+        renderer.draw_polygon(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)], (0, 255, 0), (0., 10.));
+
+        let stats = renderer.finish().unwrap();
+
+        assert_eq!(stats.polygon_count, 2);
+        assert_eq!(stats.vertex_count, 7);
+        assert_eq!(stats.viewbox, (0., 0., 100., 100.));
+    }
+
+    #[test]
+    fn png_renderer_maps_viewbox_corners_to_image_corners() {
+        let mut renderer = PngRenderer::new("test", 200, 100);
+        renderer.begin((-10., -10., 20., 20.));
+
+        assert_eq!(renderer.to_pixel((-10., -10.)), (0., 0.));
+        assert_eq!(renderer.to_pixel((10., 10.)), (200., 100.));
+    }
+
+    #[test]
+    fn png_renderer_fills_pixels_inside_a_triangle_and_not_outside() {
+        let mut renderer = PngRenderer::new("test", 10, 10);
+        renderer.begin((0., 0., 10., 10.));
+
+        let color = Rgba([255, 0, 0, 255]);
+        renderer.fill_triangle((0., 0.), (9., 0.), (0., 9.), color);
+
+        assert_eq!(*renderer.image.get_pixel(1, 1), color);
+        assert_eq!(*renderer.image.get_pixel(8, 8), Rgba([0, 0, 0, 255]));
+    }
+}
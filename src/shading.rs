@@ -0,0 +1,85 @@
+/// Directional (Lambertian) light used to shade faces by their plane
+/// normal, so vertical walls read differently from horizontal floors
+/// instead of every face showing its flat averaged texture color.
+pub struct ShadingOptions {
+    /// Direction the light travels, normalized. Faces whose outward normal
+    /// points toward `-light_dir` are lit brightest.
+    pub light_dir: (f32, f32, f32),
+    /// Minimum intensity a face keeps even when it faces away from the
+    /// light, in `0.0..=1.0`.
+    pub ambient: f32,
+}
+
+impl Default for ShadingOptions {
+    fn default() -> Self {
+        Self {
+            light_dir: normalize((0.4, 0.4, -0.8)),
+            ambient: 0.35,
+        }
+    }
+}
+
+pub fn normalize(v: (f32, f32, f32)) -> (f32, f32, f32) {
+    let len = (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt();
+    if len == 0. {
+        v
+    } else {
+        (v.0 / len, v.1 / len, v.2 / len)
+    }
+}
+
+fn dot(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1 + a.2 * b.2
+}
+
+/// `intensity = ambient + (1 - ambient) * max(0, dot(normal, -light_dir))`
+///
+/// `light_dir` is the direction the light travels, so a face is lit by how
+/// much its normal points back along that travel direction, not along it.
+pub fn intensity(normal: (f32, f32, f32), options: &ShadingOptions) -> f32 {
+    let light_dir = options.light_dir;
+    let facing = dot(normalize(normal), (-light_dir.0, -light_dir.1, -light_dir.2)).max(0.);
+    options.ambient + (1. - options.ambient) * facing
+}
+
+pub fn shade(color: (u32, u32, u32), intensity: f32) -> (u32, u32, u32) {
+    (
+        (color.0 as f32 * intensity).clamp(0.0, 255.0) as u32,
+        (color.1 as f32 * intensity).clamp(0.0, 255.0) as u32,
+        (color.2 as f32 * intensity).clamp(0.0, 255.0) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floor_is_brightest_under_the_default_downward_light() {
+        let options = ShadingOptions::default();
+
+        let floor = intensity((0., 0., 1.), &options);
+        let ceiling = intensity((0., 0., -1.), &options);
+        let wall = intensity((1., 0., 0.), &options);
+
+        assert!(floor > wall);
+        assert!(floor > ceiling);
+        assert!(floor > 0.8);
+    }
+
+    #[test]
+    fn intensity_never_drops_below_ambient() {
+        let options = ShadingOptions::default();
+
+        let ceiling = intensity((0., 0., -1.), &options);
+
+        assert!((ceiling - options.ambient).abs() < 1e-6);
+    }
+
+    #[test]
+    fn shade_clamps_instead_of_wrapping_when_intensity_exceeds_one() {
+        let shaded = shade((255, 255, 255), 1.2);
+
+        assert_eq!(shaded, (255, 255, 255));
+    }
+}